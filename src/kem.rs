@@ -0,0 +1,116 @@
+//! Hybrid KEM/DEM API for encrypting arbitrary-length payloads under an identity.
+//!
+//! [`encapsulate`]/[`decapsulate`] derive a 256-bit [`SymmetricKey`] from the
+//! encrypted [`Message`]; [`seal`]/[`open`] then use that key with AES-256-GCM
+//! so callers can encrypt payloads of arbitrary length, not just curve
+//! points. Needs an allocator, so it's kept behind the `kem` feature.
+
+extern crate alloc;
+
+use crate::{decrypt, encrypt, CipherText, Identity, Message, PublicKey, UserSecretKey};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use alloc::vec::Vec;
+use rand::Rng;
+
+const KDF_LABEL: &[u8] = b"ibe-kem-v1-key:";
+const NONCE_LEN: usize = 12;
+
+/// A 256-bit symmetric key derived by [`encapsulate`]/[`decapsulate`], for use
+/// with an AEAD such as [`seal`]/[`open`].
+pub type SymmetricKey = [u8; 32];
+
+/// Generate a random [`Message`], encrypt it for `v` under `pk`, and derive a
+/// [`SymmetricKey`] from it.
+pub fn encapsulate<R: Rng>(
+    pk: &PublicKey,
+    v: &Identity,
+    rng: &mut R,
+) -> (CipherText, SymmetricKey) {
+    let m = Message::generate(rng);
+    let c = encrypt(pk, v, &m, rng);
+    (c, derive_key(&m))
+}
+
+/// Recover the [`SymmetricKey`] [`encapsulate`] derived, given the user secret
+/// key for `v` and the ciphertext it returned.
+pub fn decapsulate(usk: &UserSecretKey, c: &CipherText) -> SymmetricKey {
+    derive_key(&decrypt(usk, c))
+}
+
+fn derive_key(m: &Message) -> SymmetricKey {
+    let m_bytes = m.to_bytes();
+    let mut buf = [0u8; KDF_LABEL.len() + 576];
+    let (label, rest) = buf.split_at_mut(KDF_LABEL.len());
+    label.copy_from_slice(KDF_LABEL);
+    rest.copy_from_slice(&m_bytes);
+
+    let hash = tiny_keccak::sha3_512(&buf);
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&hash[..32]);
+    key
+}
+
+/// Encrypt and authenticate `plaintext` under a [`SymmetricKey`] derived by
+/// [`encapsulate`], using AES-256-GCM with a fresh random nonce (sampled from
+/// `rng`) prepended to the returned ciphertext.
+pub fn seal<R: Rng>(key: &SymmetricKey, plaintext: &[u8], rng: &mut R) -> Vec<u8> {
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rng.fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let cipher = Aes256Gcm::new(key.into());
+    let mut ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .expect("AES-256-GCM encryption of an in-memory buffer cannot fail");
+
+    let mut res = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    res.extend_from_slice(&nonce_bytes);
+    res.append(&mut ciphertext);
+    res
+}
+
+/// Decrypt and verify a payload produced by [`seal`] under the corresponding
+/// [`SymmetricKey`] from [`decapsulate`].
+///
+/// Returns `None` if authentication fails or `sealed` is too short to contain
+/// a nonce.
+pub fn open(key: &SymmetricKey, sealed: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(key.into());
+    cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::perform_default;
+
+    #[test]
+    fn seal_open_round_trip() {
+        let mut rng = rand::thread_rng();
+        let default = perform_default();
+
+        let (c, key) = encapsulate(&default.pk, &default.kid, &mut rng);
+        let key2 = decapsulate(&default.usk, &c);
+        assert_eq!(key, key2);
+
+        let plaintext = b"the quick brown fox jumps over the lazy dog";
+        let sealed = seal(&key, plaintext, &mut rng);
+        let opened = open(&key2, &sealed).unwrap();
+        assert_eq!(&opened[..], &plaintext[..]);
+    }
+
+    #[test]
+    fn open_rejects_tampered_ciphertext() {
+        let mut rng = rand::thread_rng();
+        let key = [0u8; 32];
+        let mut sealed = seal(&key, b"hello world", &mut rng);
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(open(&key, &sealed).is_none());
+    }
+}
@@ -14,10 +14,27 @@
 mod util;
 use crate::util::*;
 
+#[cfg(test)]
+mod test_support;
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "serde")]
+pub use serde;
+
+#[cfg(feature = "alloc")]
+pub mod threshold;
+
+#[cfg(feature = "kem")]
+pub mod kem;
+
+#[cfg(feature = "kem")]
+pub mod fo;
+
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use bls12_381::{G1Affine, G2Affine, G2Projective, Gt, Scalar};
 use rand::Rng;
-use subtle::{Choice, ConditionallySelectable, CtOption};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
 
 const HASH_BIT_LEN: usize = 512;
 const HASH_BYTE_LEN: usize = HASH_BIT_LEN / 8;
@@ -28,6 +45,7 @@ const CHUNKS: usize = HASH_BYTE_LEN / CHUNKSIZE;
 
 const PARAMETERSIZE: usize = CHUNKS * 96;
 const PUBLICKEYSIZE: usize = 2 * 48 + 2 * 96 + PARAMETERSIZE;
+const IDENTITYSIZE: usize = CHUNKS * 32;
 
 #[derive(Default, Clone, Copy, PartialEq, Debug)]
 struct Parameters([G2Affine; CHUNKS]);
@@ -122,7 +140,15 @@ impl PublicKey {
 pub struct Identity([Scalar; CHUNKS]);
 
 /// Secret key parameter generated by the PKG used to extract user secret keys.
-#[derive(Clone, Copy, Debug, PartialEq)]
+///
+/// Equality is constant-time (see [`ConstantTimeEq`]) so that comparing two
+/// secret keys cannot leak timing information. With the `zeroize` feature
+/// enabled, the key material is wiped from memory when this value is
+/// dropped; since a type with a `Drop` impl cannot be `Copy`, that feature
+/// also drops `Copy` from this type.
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[cfg_attr(feature = "zeroize", derive(zeroize::Zeroize, zeroize::ZeroizeOnDrop))]
 pub struct SecretKey {
     g2prime: G2Affine,
 }
@@ -137,8 +163,28 @@ impl SecretKey {
     }
 }
 
+impl ConstantTimeEq for SecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.g2prime.ct_eq(&other.g2prime)
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
 /// Points on the paired curves that form the user secret key.
-#[derive(Clone, Copy, Debug, PartialEq)]
+///
+/// Equality is constant-time (see [`ConstantTimeEq`]) so that comparing two
+/// user secret keys cannot leak timing information. With the `zeroize`
+/// feature enabled, the key material is wiped from memory when this value is
+/// dropped; since a type with a `Drop` impl cannot be `Copy`, that feature
+/// also drops `Copy` from this type.
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[cfg_attr(feature = "zeroize", derive(zeroize::Zeroize, zeroize::ZeroizeOnDrop))]
 pub struct UserSecretKey {
     d1: G2Affine,
     d2: G1Affine,
@@ -163,7 +209,26 @@ impl UserSecretKey {
     }
 }
 
+impl ConstantTimeEq for UserSecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.d1.ct_eq(&other.d1) & self.d2.ct_eq(&other.d2)
+    }
+}
+
+impl PartialEq for UserSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
 /// Encrypted message. Can only be decrypted with an user secret key.
+///
+/// `to_bytes`/`from_bytes` use the uncompressed 720-byte encoding. A
+/// compressed, torus-based encoding of `c1` (bwesterb/ibe#chunk0-5) needs
+/// access to `c1`'s Fp6/Fp12 tower coefficients, which `bls12_381::Gt`
+/// does not expose publicly; that request is descoped pending either an
+/// upstream `bls12_381` API for it or a fork that exposes the tower, and
+/// is not implemented here.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CipherText {
     c1: Gt,
@@ -175,7 +240,7 @@ impl CipherText {
     pub fn to_bytes(&self) -> [u8; 720] {
         let mut res = [0u8; 720];
         let (c1, c2, c3) = mut_array_refs![&mut res, 576, 48, 96];
-        *c1 = self.c1.to_uncompressed(); // TODO implement compressed
+        *c1 = self.c1.to_uncompressed();
         *c2 = self.c2.to_compressed();
         *c3 = self.c3.to_compressed();
         res
@@ -192,9 +257,18 @@ impl CipherText {
     }
 }
 
+impl ConstantTimeEq for CipherText {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.c1.ct_eq(&other.c1) & self.c2.ct_eq(&other.c2) & self.c3.ct_eq(&other.c3)
+    }
+}
+
 /// A point on the paired curve that can be encrypted and decrypted.
 ///
-/// You can use the byte representation to derive an AES key.
+/// You can use the byte representation to derive an AES key. `to_bytes`
+/// uses the uncompressed 576-byte encoding; see [`CipherText`]'s doc
+/// comment for why a compressed encoding (bwesterb/ibe#chunk0-5) isn't
+/// implemented here.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Message(Gt);
 
@@ -262,10 +336,13 @@ pub fn extract_usk<R: Rng>(
     UserSecretKey { d1, d2 }
 }
 
-/// Encrypt a message using the PKG public key and an identity.
-pub fn encrypt<R: Rng>(pk: &PublicKey, v: &Identity, m: &Message, rng: &mut R) -> CipherText {
-    let t = rand_scalar(rng);
-
+/// Encrypt a message using the PKG public key and an identity, under the
+/// given randomizer `t`.
+///
+/// Shared by [`encrypt`] (which samples `t` at random, giving only CPA
+/// security) and [`fo::encrypt_cca`] (which derives `t` deterministically from
+/// the message, as part of a Fujisaki–Okamoto transform giving CCA security).
+fn encrypt_with_t(pk: &PublicKey, v: &Identity, m: &Message, t: Scalar) -> CipherText {
     let mut c3coll: G2Projective = pk.uprime.into();
     for (ui, vi) in pk.u.0.iter().zip(&v.0) {
         c3coll += ui * vi;
@@ -278,6 +355,11 @@ pub fn encrypt<R: Rng>(pk: &PublicKey, v: &Identity, m: &Message, rng: &mut R) -
     CipherText { c1, c2, c3 }
 }
 
+/// Encrypt a message using the PKG public key and an identity.
+pub fn encrypt<R: Rng>(pk: &PublicKey, v: &Identity, m: &Message, rng: &mut R) -> CipherText {
+    encrypt_with_t(pk, v, m, rand_scalar(rng))
+}
+
 /// Decrypt ciphertext to a message using a user secret key.
 pub fn decrypt(usk: &UserSecretKey, c: &CipherText) -> Message {
     let num = bls12_381::pairing(&usk.d2, &c.c3);
@@ -311,46 +393,33 @@ impl Identity {
     pub fn derive_str(s: &str) -> Identity {
         Self::derive(s.as_bytes())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    const ID: &'static str = "email:w.geraedts@sarif.nl";
 
-    #[allow(dead_code)]
-    struct DefaultSubResults {
-        kid: Identity,
-        m: Message,
-        pk: PublicKey,
-        sk: SecretKey,
-        usk: UserSecretKey,
-        c: CipherText,
+    pub fn to_bytes(&self) -> [u8; IDENTITYSIZE] {
+        let mut res = [0u8; IDENTITYSIZE];
+        for i in 0..CHUNKS {
+            *array_mut_ref![&mut res, i * 32, 32] = self.0[i].to_bytes();
+        }
+        res
     }
 
-    fn perform_default() -> DefaultSubResults {
-        let mut rng = rand::thread_rng();
-
-        let id = ID.as_bytes();
-        let kid = Identity::derive(id);
-
-        let m = Message::generate(&mut rng);
-
-        let (pk, sk) = setup(&mut rng);
-        let usk = extract_usk(&pk, &sk, &kid, &mut rng);
-
-        let c = encrypt(&pk, &kid, &m, &mut rng);
-
-        DefaultSubResults {
-            kid,
-            m,
-            pk,
-            sk,
-            usk,
-            c,
+    pub fn from_bytes(bytes: &[u8; IDENTITYSIZE]) -> CtOption<Self> {
+        let mut res = [Scalar::zero(); CHUNKS];
+        let mut is_some = Choice::from(1u8);
+        for i in 0..CHUNKS {
+            is_some &= Scalar::from_bytes(array_ref![bytes, i * 32, 32])
+                .map(|s| {
+                    res[i] = s;
+                })
+                .is_some();
         }
+        CtOption::new(Identity(res), is_some)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{perform_default, ID};
 
     #[test]
     fn eq_encrypt_decrypt() {
@@ -400,5 +469,55 @@ mod tests {
             result.c,
             CipherText::from_bytes(&result.c.to_bytes()).unwrap()
         );
+        assert_eq!(
+            result.kid,
+            Identity::from_bytes(&result.kid.to_bytes()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn eq_serde_bincode() {
+        let result = perform_default();
+
+        let encoded = bincode::serialize(&result.pk).unwrap();
+        let decoded: PublicKey = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(result.pk, decoded);
+
+        let encoded = bincode::serialize(&result.c).unwrap();
+        let decoded: CipherText = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(result.c, decoded);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn eq_serde_json() {
+        let result = perform_default();
+
+        let encoded = serde_json::to_string(&result.usk).unwrap();
+        let decoded: UserSecretKey = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(result.usk, decoded);
+    }
+
+    #[test]
+    fn ct_eq_secret_keys() {
+        let result = perform_default();
+        let other = perform_default();
+
+        assert!(bool::from(result.sk.ct_eq(&result.sk)));
+        assert!(!bool::from(result.sk.ct_eq(&other.sk)));
+
+        assert!(bool::from(result.usk.ct_eq(&result.usk)));
+        assert!(!bool::from(result.usk.ct_eq(&other.usk)));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize_secret_keys() {
+        use zeroize::Zeroize;
+
+        let mut result = perform_default();
+        result.sk.zeroize();
+        result.usk.zeroize();
     }
 }
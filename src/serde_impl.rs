@@ -0,0 +1,88 @@
+//! Optional [`serde`] support for this crate's key and ciphertext types.
+//!
+//! Every type in this module is (de)serialized through its existing fixed-size
+//! `to_bytes`/`from_bytes` methods: as a byte tuple for compact, non-human-readable
+//! formats (e.g. `bincode`), and as a hex string for human-readable formats
+//! (e.g. `serde_json`). This mirrors the approach `blsttc` takes for its own
+//! curve-point types, and keeps this `#![no_std]` crate free of any allocation
+//! requirement.
+
+use crate::{CipherText, Identity, Message, PublicKey, SecretKey, UserSecretKey};
+use core::fmt;
+use core::marker::PhantomData;
+use serde::de::{Error as DeError, SeqAccess, Visitor};
+use serde::ser::{Error as SerError, SerializeTuple};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! impl_serde_for_fixed_size {
+    ($ty:ty, $size:expr, $name:expr) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let bytes = self.to_bytes();
+                if serializer.is_human_readable() {
+                    let mut hex_buf = [0u8; $size * 2];
+                    hex::encode_to_slice(&bytes[..], &mut hex_buf).map_err(S::Error::custom)?;
+                    let s = core::str::from_utf8(&hex_buf).map_err(S::Error::custom)?;
+                    serializer.serialize_str(s)
+                } else {
+                    let mut tup = serializer.serialize_tuple($size)?;
+                    for b in &bytes[..] {
+                        tup.serialize_element(b)?;
+                    }
+                    tup.end()
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct FixedSizeVisitor(PhantomData<$ty>);
+
+                impl<'de> Visitor<'de> for FixedSizeVisitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        write!(f, "a {} byte {}", $size, $name)
+                    }
+
+                    fn visit_str<E: DeError>(self, v: &str) -> Result<Self::Value, E> {
+                        if v.len() != $size * 2 {
+                            return Err(E::invalid_length(v.len(), &self));
+                        }
+                        let mut bytes = [0u8; $size];
+                        hex::decode_to_slice(v, &mut bytes).map_err(E::custom)?;
+                        Option::from(<$ty>::from_bytes(&bytes))
+                            .ok_or_else(|| E::custom(concat!("invalid ", $name)))
+                    }
+
+                    fn visit_seq<A: SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> Result<Self::Value, A::Error> {
+                        let mut bytes = [0u8; $size];
+                        for (i, b) in bytes.iter_mut().enumerate() {
+                            *b = seq
+                                .next_element()?
+                                .ok_or_else(|| DeError::invalid_length(i, &self))?;
+                        }
+                        Option::from(<$ty>::from_bytes(&bytes))
+                            .ok_or_else(|| DeError::custom(concat!("invalid ", $name)))
+                    }
+                }
+
+                if deserializer.is_human_readable() {
+                    deserializer.deserialize_str(FixedSizeVisitor(PhantomData))
+                } else {
+                    deserializer.deserialize_tuple($size, FixedSizeVisitor(PhantomData))
+                }
+            }
+        }
+    };
+}
+
+impl_serde_for_fixed_size!(PublicKey, crate::PUBLICKEYSIZE, "PublicKey");
+impl_serde_for_fixed_size!(SecretKey, 96, "SecretKey");
+impl_serde_for_fixed_size!(UserSecretKey, 144, "UserSecretKey");
+impl_serde_for_fixed_size!(CipherText, 720, "CipherText");
+impl_serde_for_fixed_size!(Message, 576, "Message");
+impl_serde_for_fixed_size!(Identity, crate::IDENTITYSIZE, "Identity");
@@ -0,0 +1,38 @@
+//! Shared fixtures for this crate's test modules.
+
+use crate::*;
+
+pub(crate) const ID: &str = "email:w.geraedts@sarif.nl";
+
+#[allow(dead_code)]
+pub(crate) struct DefaultSubResults {
+    pub(crate) kid: Identity,
+    pub(crate) m: Message,
+    pub(crate) pk: PublicKey,
+    pub(crate) sk: SecretKey,
+    pub(crate) usk: UserSecretKey,
+    pub(crate) c: CipherText,
+}
+
+pub(crate) fn perform_default() -> DefaultSubResults {
+    let mut rng = rand::thread_rng();
+
+    let id = ID.as_bytes();
+    let kid = Identity::derive(id);
+
+    let m = Message::generate(&mut rng);
+
+    let (pk, sk) = setup(&mut rng);
+    let usk = extract_usk(&pk, &sk, &kid, &mut rng);
+
+    let c = encrypt(&pk, &kid, &m, &mut rng);
+
+    DefaultSubResults {
+        kid,
+        m,
+        pk,
+        sk,
+        usk,
+        c,
+    }
+}
@@ -0,0 +1,222 @@
+//! Distributed Private Key Generator (PKG) via Shamir secret sharing.
+//!
+//! No single share holder holds the master secret `alpha`: `setup_threshold`
+//! acts as a dealer that splits it into `n` shares of which any `t` suffice,
+//! but `t - 1` reveal nothing, to extract user secret keys.
+
+extern crate alloc;
+
+use crate::util::*;
+use crate::{Identity, PublicKey, UserSecretKey};
+use alloc::vec::Vec;
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use rand::Rng;
+use subtle::{Choice, ConstantTimeEq};
+
+/// A share of the master secret key held by one party of the distributed PKG.
+///
+/// Equality is constant-time (see [`ConstantTimeEq`]) so that comparing two
+/// shares cannot leak timing information. With the `zeroize` feature
+/// enabled, the key material is wiped from memory when this value is
+/// dropped; since a type with a `Drop` impl cannot be `Copy`, that feature
+/// also drops `Copy` from this type.
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[cfg_attr(feature = "zeroize", derive(zeroize::Zeroize, zeroize::ZeroizeOnDrop))]
+pub struct SecretKeyShare {
+    index: u64,
+    g2prime: G2Affine,
+}
+
+impl ConstantTimeEq for SecretKeyShare {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.index.ct_eq(&other.index) & self.g2prime.ct_eq(&other.g2prime)
+    }
+}
+
+impl PartialEq for SecretKeyShare {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+/// A share of an user secret key, produced by one party of the distributed PKG.
+///
+/// Any `t` of these, each from a distinct [`SecretKeyShare`], can be combined
+/// with [`combine_usk`] into a regular [`UserSecretKey`].
+///
+/// Equality is constant-time (see [`ConstantTimeEq`]) so that comparing two
+/// shares cannot leak timing information. With the `zeroize` feature
+/// enabled, the key material is wiped from memory when this value is
+/// dropped; since a type with a `Drop` impl cannot be `Copy`, that feature
+/// also drops `Copy` from this type.
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[cfg_attr(feature = "zeroize", derive(zeroize::Zeroize, zeroize::ZeroizeOnDrop))]
+pub struct UserSecretKeyShare {
+    index: u64,
+    d1: G2Affine,
+    d2: G1Affine,
+}
+
+impl ConstantTimeEq for UserSecretKeyShare {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.index.ct_eq(&other.index) & self.d1.ct_eq(&other.d1) & self.d2.ct_eq(&other.d2)
+    }
+}
+
+impl PartialEq for UserSecretKeyShare {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+fn lagrange_coefficient(index: u64, other_indices: &[u64]) -> Scalar {
+    let i = Scalar::from(index);
+    let mut lambda = Scalar::one();
+    for &j in other_indices {
+        if j == index {
+            continue;
+        }
+        let j = Scalar::from(j);
+        lambda *= j * (j - i).invert().unwrap();
+    }
+    lambda
+}
+
+/// Generate a [`PublicKey`] together with `n` [`SecretKeyShare`]s of which any
+/// `t` suffice to extract user secret keys, but `t - 1` reveal nothing about
+/// the master secret.
+///
+/// Shares are indexed `1..=n`. The master secret `alpha` is `f(0)` of the
+/// random degree-`(t - 1)` polynomial `f` sampled here; it is computed during
+/// this call (to derive the public key) but is not returned, so after setup
+/// no single share holds it.
+pub fn setup_threshold<R: Rng>(n: u64, t: u64, rng: &mut R) -> (PublicKey, Vec<SecretKeyShare>) {
+    assert!(t >= 1, "setup_threshold needs a threshold of at least 1");
+    assert!(
+        t <= n,
+        "setup_threshold needs at least t shares to hand out"
+    );
+
+    let g: G1Affine = rand_g1(rng).into();
+    let g2: G2Affine = rand_g2(rng).into();
+    let uprime: G2Affine = rand_g2(rng).into();
+
+    let mut u = crate::Parameters::default();
+    for ui in u.0.iter_mut() {
+        *ui = rand_g2(rng).into();
+    }
+
+    let coefficients: Vec<Scalar> = (0..t).map(|_| rand_scalar(rng)).collect();
+    let alpha = coefficients[0];
+    let g1 = (g * alpha).into();
+
+    let pk = PublicKey {
+        g,
+        g1,
+        g2,
+        uprime,
+        u,
+    };
+
+    let shares = (1..=n)
+        .map(|index| {
+            let x = Scalar::from(index);
+            let mut f_x = Scalar::zero();
+            for coefficient in coefficients.iter().rev() {
+                f_x = f_x * x + coefficient;
+            }
+            SecretKeyShare {
+                index,
+                g2prime: (g2 * f_x).into(),
+            }
+        })
+        .collect();
+
+    (pk, shares)
+}
+
+/// Extract a partial user secret key for a given identity from one
+/// [`SecretKeyShare`] of the distributed PKG.
+pub fn extract_usk_partial<R: Rng>(
+    pk: &PublicKey,
+    share: &SecretKeyShare,
+    v: &Identity,
+    rng: &mut R,
+) -> UserSecretKeyShare {
+    let mut ucoll: G2Projective = pk.uprime.into();
+    for (ui, vi) in pk.u.0.iter().zip(&v.0) {
+        ucoll += ui * vi;
+    }
+
+    let r = rand_scalar(rng);
+    let d1 = (share.g2prime + (ucoll * r)).into();
+    let d2 = (pk.g * r).into();
+
+    UserSecretKeyShare {
+        index: share.index,
+        d1,
+        d2,
+    }
+}
+
+/// Combine `t` or more [`UserSecretKeyShare`]s, each from a distinct party of
+/// the distributed PKG, into a regular [`UserSecretKey`] decodable by [`crate::decrypt`].
+///
+/// Applies the Lagrange coefficients `λᵢ = ∏_{j≠i} j / (j - i)` so that
+/// `Σ λᵢ·g2prime_i` reconstructs `g2·alpha` and the per-share randomizers
+/// combine into a single valid randomizer `R = Σ λᵢ·rᵢ`.
+pub fn combine_usk(shares: &[UserSecretKeyShare]) -> UserSecretKey {
+    assert!(!shares.is_empty(), "combine_usk needs at least one share");
+
+    let indices: Vec<u64> = shares.iter().map(|share| share.index).collect();
+
+    let mut shares = shares.iter();
+    let first = shares.next().unwrap();
+    let first_lambda = lagrange_coefficient(first.index, &indices);
+    let mut d1: G2Projective = first.d1 * first_lambda;
+    let mut d2: G1Projective = first.d2 * first_lambda;
+
+    for share in shares {
+        let lambda = lagrange_coefficient(share.index, &indices);
+        d1 += share.d1 * lambda;
+        d2 += share.d2 * lambda;
+    }
+
+    UserSecretKey {
+        d1: d1.into(),
+        d2: d2.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encrypt;
+    use crate::test_support::ID;
+
+    #[test]
+    fn threshold_reconstructs_from_any_t_shares() {
+        let mut rng = rand::thread_rng();
+        let (n, t) = (5, 3);
+
+        let (pk, key_shares) = setup_threshold(n, t, &mut rng);
+        let kid = Identity::derive(ID.as_bytes());
+
+        let m = crate::Message::generate(&mut rng);
+        let c = encrypt(&pk, &kid, &m, &mut rng);
+
+        // Any subset of t shares should reconstruct a working user secret key,
+        // regardless of which shares are chosen.
+        for subset in [&key_shares[0..3], &key_shares[1..4], &key_shares[2..5]] {
+            let partials: Vec<UserSecretKeyShare> = subset
+                .iter()
+                .map(|share| extract_usk_partial(&pk, share, &kid, &mut rng))
+                .collect();
+
+            let usk = combine_usk(&partials);
+            assert_eq!(m, crate::decrypt(&usk, &c));
+        }
+    }
+}